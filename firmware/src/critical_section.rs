@@ -0,0 +1,18 @@
+//! Interrupt-masking critical section, for the handful of places state is
+//! shared between thread context and an interrupt handler. Single-core, so
+//! masking interrupts for the duration is sufficient — no spinlock needed
+//! (and a spinlock would be actively wrong: it can deadlock against the
+//! very interrupt it's waiting on).
+
+/// Runs `f` with interrupts masked, then restores the prior interrupt
+/// state. Keep the closure short; nothing here can be woken by an
+/// interrupt while it runs.
+#[cfg(feature = "cortex-m-target")]
+pub fn free<R>(f: impl FnOnce() -> R) -> R {
+    cortex_m::interrupt::free(|_| f())
+}
+
+#[cfg(feature = "riscv-target")]
+pub fn free<R>(f: impl FnOnce() -> R) -> R {
+    riscv::interrupt::free(f)
+}
@@ -0,0 +1,74 @@
+//! Driver for the Analog Devices AD7172 multichannel 24-bit sigma-delta
+//! ADC. Talks over any [`SpiBus`] (hardware `Spi` or bit-banged `SoftSpi`).
+//!
+//! Every transaction starts with a communication register write selecting
+//! read/write and the target register, followed by that register's width
+//! in bytes. Data-ready is signalled on the status register's high bit.
+
+use crate::peripheral::spi::SpiBus;
+
+const COMM_READ: u8 = 0x40;
+
+const REG_STATUS: u8 = 0x00;
+const REG_CHANNEL0: u8 = 0x10;
+const REG_SETUPCON0: u8 = 0x20;
+const REG_FILTCON0: u8 = 0x28;
+const REG_DATA: u8 = 0x04;
+
+const STATUS_READY: u32 = 0x80;
+const STATUS_CHANNEL_MASK: u32 = 0x03;
+
+pub struct Ad7172<B: SpiBus> {
+    spi: B,
+}
+
+impl<B: SpiBus> Ad7172<B> {
+    pub fn new(spi: B) -> Self {
+        Self { spi }
+    }
+
+    /// Enables `channel`, routes it through setup/filter slot `setup`, and
+    /// writes `filter_word` into that setup's filter-configuration register.
+    pub fn configure_channel(&mut self, channel: u8, setup: u8, filter_word: u16) {
+        let enable_and_route = 0x8000 | ((setup as u32) << 12);
+        self.write_reg(REG_CHANNEL0 + channel, 2, enable_and_route);
+        self.write_reg(REG_SETUPCON0 + setup, 2, 0x1000);
+        self.write_reg(REG_FILTCON0 + setup, 2, filter_word as u32);
+    }
+
+    /// Polls the status register; returns the channel and signed 24-bit
+    /// sample of the next ready conversion, or `None` if nothing is ready.
+    pub fn read_data(&mut self) -> Option<(u8, i32)> {
+        let status = self.read_reg(REG_STATUS, 1);
+        if status & STATUS_READY == 0 {
+            return None;
+        }
+        let channel = (status & STATUS_CHANNEL_MASK) as u8;
+        let raw = self.read_reg(REG_DATA, 3);
+        Some((channel, sign_extend_24(raw)))
+    }
+
+    fn read_reg(&mut self, addr: u8, width: usize) -> u32 {
+        self.spi.transfer(COMM_READ | addr);
+        let mut value = 0u32;
+        for _ in 0..width {
+            value = (value << 8) | self.spi.transfer(0x00) as u32;
+        }
+        value
+    }
+
+    fn write_reg(&mut self, addr: u8, width: usize, value: u32) {
+        self.spi.transfer(addr);
+        for i in (0..width).rev() {
+            self.spi.transfer((value >> (8 * i)) as u8);
+        }
+    }
+}
+
+fn sign_extend_24(raw: u32) -> i32 {
+    if raw & 0x0080_0000 != 0 {
+        (raw | 0xFF00_0000) as i32
+    } else {
+        raw as i32
+    }
+}
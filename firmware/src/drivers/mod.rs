@@ -0,0 +1,2 @@
+pub mod ad7172;
+pub mod pca9539;
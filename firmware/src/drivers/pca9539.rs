@@ -0,0 +1,108 @@
+//! Driver for a PCA9539-style 16-bit I2C I/O expander: a configuration
+//! register sets each pin's direction, the output register drives levels,
+//! and the input register reads them back. Used to extend `Gpio` past the
+//! MCU's own pins once those run out.
+
+use core::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+
+use crate::peripheral::i2c::I2c;
+
+const EXPANDER_ADDR: u8 = 0x74;
+
+const REG_INPUT0: u8 = 0x00;
+const REG_OUTPUT0: u8 = 0x02;
+const REG_CONFIG0: u8 = 0x06;
+
+/// The chip only has 16 pins; `1 << pin` on anything wider would panic in
+/// debug builds and wrap in release.
+const PIN_COUNT: u8 = 16;
+
+/// Output-register bits `service()` exclusively owns to mirror link/
+/// activity status; `set_pin`/`set_outputs` are free to drive every other
+/// bit, so `Command::SetGpio` and the heartbeat's status LEDs coexist.
+const STATUS_LED_MASK: u16 = 0x0003;
+
+/// Mirrors the chip's output register, so `set_pin` can read-modify-write a
+/// single bit without an extra I2C round trip, and `service` can tell
+/// whether a push is actually needed.
+static OUTPUT_CACHE: AtomicU16 = AtomicU16::new(0);
+
+/// Last bitmap `service` pushed to the chip, and whether it has pushed yet.
+static VIRTUAL_LED_CACHE: AtomicU16 = AtomicU16::new(0);
+static VIRTUAL_LED_PUSHED: AtomicBool = AtomicBool::new(false);
+
+/// A handle onto the expander. Cheap and repeatable like `Gpio`/`Timer` —
+/// the chip's actual state lives in the statics above, shared by every
+/// handle.
+pub struct Pca9539;
+
+impl Pca9539 {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Configures every pin set in `output_mask` as an output and the rest
+    /// as inputs, per the PCA9539's active-low configuration register.
+    pub fn init(&mut self, output_mask: u16) {
+        let config = !output_mask;
+        self.write16(REG_CONFIG0, config);
+        OUTPUT_CACHE.store(0, Ordering::Relaxed);
+        defmt::debug!("PCA9539 expander initialized");
+    }
+
+    /// Drives a single expander output pin. Pins covered by
+    /// `STATUS_LED_MASK` belong to `service()`'s status LEDs, not to
+    /// host-addressable GPIO, so they're rejected here rather than being
+    /// clobbered on the next heartbeat.
+    pub fn set_pin(&mut self, pin: u8, state: bool) {
+        if pin >= PIN_COUNT {
+            defmt::warn!("PCA9539 pin {} out of range, ignoring", pin);
+            return;
+        }
+        if (1u16 << pin) & STATUS_LED_MASK != 0 {
+            defmt::warn!("PCA9539 pin {} is reserved for status LEDs, ignoring", pin);
+            return;
+        }
+        let mut bits = OUTPUT_CACHE.load(Ordering::Relaxed);
+        if state {
+            bits |= 1 << pin;
+        } else {
+            bits &= !(1 << pin);
+        }
+        self.set_outputs(bits);
+    }
+
+    pub fn set_outputs(&mut self, bits: u16) {
+        OUTPUT_CACHE.store(bits, Ordering::Relaxed);
+        self.write16(REG_OUTPUT0, bits);
+    }
+
+    pub fn read_inputs(&mut self) -> u16 {
+        self.read16(REG_INPUT0)
+    }
+
+    /// Called once per heartbeat with a bitmap of virtual status LEDs (e.g.
+    /// link/activity); only issues an I2C write when `STATUS_LED_MASK`'s
+    /// bits actually changed since the last call, and only ever touches
+    /// those bits — any expander output pin a host set via `Command::SetGpio`
+    /// is left untouched, useful when the MCU's own GPIO pins are exhausted.
+    pub fn service(&mut self, status: u16) {
+        let masked = status & STATUS_LED_MASK;
+        let previous = VIRTUAL_LED_CACHE.swap(masked, Ordering::Relaxed);
+        let first_push = !VIRTUAL_LED_PUSHED.swap(true, Ordering::Relaxed);
+        if first_push || previous != masked {
+            let bits = (OUTPUT_CACHE.load(Ordering::Relaxed) & !STATUS_LED_MASK) | masked;
+            self.set_outputs(bits);
+        }
+    }
+
+    fn write16(&mut self, reg: u8, value: u16) {
+        I2c::new().write(EXPANDER_ADDR, &[reg, value as u8, (value >> 8) as u8]);
+    }
+
+    fn read16(&mut self, reg: u8) -> u16 {
+        let mut buf = [0u8; 2];
+        I2c::new().write_read(EXPANDER_ADDR, reg, &mut buf);
+        (buf[1] as u16) << 8 | buf[0] as u16
+    }
+}
@@ -0,0 +1,302 @@
+//! No-alloc async executor with an integrated timer queue.
+//!
+//! Tasks are plain [`Future`] implementations placed in `'static`
+//! [`TaskStorage`] statics (no macros, no heap) and linked intrusively, so
+//! the number of tasks isn't bounded by a fixed-size array. `Executor::run`
+//! drains the run queue, then sleeps the core until the nearest timer
+//! expiry or an interrupt wakes a task back onto the run queue.
+
+use core::cell::{Cell, UnsafeCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use fugit::Instant;
+
+/// The clock the executor's timer queue is keyed off: the same
+/// millisecond-tick instant `Timer::get_tick` already returns.
+pub type Clock = Instant<u32, 1, 1000>;
+
+struct TaskHeader {
+    poll_fn: unsafe fn(*mut ()),
+    task_ptr: Cell<*mut ()>,
+    run_next: AtomicPtr<TaskHeader>,
+    queued: AtomicBool,
+    timer_next: Cell<*mut TaskHeader>,
+    expires_at: Cell<Option<Clock>>,
+}
+
+unsafe impl Sync for TaskHeader {}
+
+impl TaskHeader {
+    const fn new(poll_fn: unsafe fn(*mut ())) -> Self {
+        Self {
+            poll_fn,
+            task_ptr: Cell::new(core::ptr::null_mut()),
+            run_next: AtomicPtr::new(core::ptr::null_mut()),
+            queued: AtomicBool::new(false),
+            timer_next: Cell::new(core::ptr::null_mut()),
+            expires_at: Cell::new(None),
+        }
+    }
+}
+
+/// Static storage for one task's future. Declare one per task as a
+/// `static`, then hand it a future with [`TaskStorage::spawn`].
+pub struct TaskStorage<F: Future<Output = ()> + 'static> {
+    future: UnsafeCell<Option<F>>,
+    header: TaskHeader,
+}
+
+unsafe impl<F: Future<Output = ()> + 'static> Sync for TaskStorage<F> {}
+
+impl<F: Future<Output = ()> + 'static> TaskStorage<F> {
+    pub const fn new() -> Self {
+        Self {
+            future: UnsafeCell::new(None),
+            header: TaskHeader::new(Self::poll_task),
+        }
+    }
+
+    /// Places `future` into this storage and returns a token to hand to
+    /// [`Executor::spawn`]. Must only be called once per storage.
+    pub fn spawn(&'static self, future: F) -> SpawnToken {
+        unsafe { *self.future.get() = Some(future) };
+        self.header.task_ptr.set(self as *const _ as *mut ());
+        SpawnToken {
+            header: &self.header as *const TaskHeader as *mut TaskHeader,
+        }
+    }
+
+    unsafe fn poll_task(p: *mut ()) {
+        let this = &*(p as *const Self);
+        let waker = make_waker(&this.header as *const TaskHeader as *const ());
+        let mut cx = Context::from_waker(&waker);
+
+        // Polled in place inside the `UnsafeCell`: the future's address is
+        // `this.future`'s address, which never changes for the `'static`
+        // storage's lifetime, so pinning it here upholds `Pin`'s no-move
+        // guarantee even for address-sensitive futures. `poll_task` is only
+        // ever called from `Executor::run`'s single-threaded loop, never
+        // re-entrantly, so this is the only live reference to the slot.
+        let slot = &mut *this.future.get();
+        if let Some(fut) = slot {
+            let pinned = Pin::new_unchecked(fut);
+            if pinned.poll(&mut cx).is_ready() {
+                *slot = None;
+            }
+        }
+    }
+}
+
+/// A future placed in a [`TaskStorage`], ready to hand to [`Executor::spawn`].
+pub struct SpawnToken {
+    header: *mut TaskHeader,
+}
+
+/// The run-queue and timer-queue scheduler. There is one instance, `EXECUTOR`,
+/// shared by every task and by the timer interrupt.
+pub struct Executor {
+    run_queue: AtomicPtr<TaskHeader>,
+    timer_queue: Cell<*mut TaskHeader>,
+}
+
+unsafe impl Sync for Executor {}
+
+pub static EXECUTOR: Executor = Executor::new();
+
+impl Executor {
+    const fn new() -> Self {
+        Self {
+            run_queue: AtomicPtr::new(core::ptr::null_mut()),
+            timer_queue: Cell::new(core::ptr::null_mut()),
+        }
+    }
+
+    pub fn spawn(&'static self, token: SpawnToken) {
+        self.enqueue(token.header);
+    }
+
+    fn enqueue(&'static self, header: *mut TaskHeader) {
+        unsafe {
+            if (*header).queued.swap(true, Ordering::AcqRel) {
+                return;
+            }
+        }
+        let mut head = self.run_queue.load(Ordering::Acquire);
+        loop {
+            unsafe { (*header).run_next.store(head, Ordering::Relaxed) };
+            match self.run_queue.compare_exchange_weak(
+                head,
+                header,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    fn pop_ready(&'static self) -> Option<*mut TaskHeader> {
+        let mut head = self.run_queue.load(Ordering::Acquire);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+            let next = unsafe { (*head).run_next.load(Ordering::Relaxed) };
+            match self.run_queue.compare_exchange_weak(
+                head,
+                next,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(head),
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    /// Inserts `header` into the sorted timer queue. Masks interrupts for
+    /// the whole walk-and-link: `on_timer_interrupt` mutates the same list
+    /// from interrupt context, and an interrupt landing mid-insert here
+    /// would corrupt it (a lost node or a cycle).
+    fn register_timer(&'static self, header: *mut TaskHeader, at: Clock) {
+        crate::critical_section::free(|| {
+            unsafe { (*header).expires_at.set(Some(at)) };
+            let mut slot = &self.timer_queue;
+            loop {
+                let next = slot.get();
+                if next.is_null() || at < unsafe { (*next).expires_at.get() }.unwrap() {
+                    unsafe { (*header).timer_next.set(next) };
+                    slot.set(header);
+                    return;
+                }
+                slot = unsafe { &(*next).timer_next };
+            }
+        })
+    }
+
+    fn next_expiry(&'static self) -> Option<Clock> {
+        crate::critical_section::free(|| {
+            let head = self.timer_queue.get();
+            if head.is_null() {
+                None
+            } else {
+                unsafe { (*head).expires_at.get() }
+            }
+        })
+    }
+
+    /// Runs every ready task to completion of this poll cycle, then sleeps
+    /// until the nearest timer expiry or an interrupt wakes the core.
+    pub fn run(&'static self) -> ! {
+        loop {
+            while let Some(header) = self.pop_ready() {
+                unsafe {
+                    (*header).queued.store(false, Ordering::Release);
+                    let poll_fn = (*header).poll_fn;
+                    let task_ptr = (*header).task_ptr.get();
+                    poll_fn(task_ptr);
+                }
+            }
+
+            if let Some(expires_at) = self.next_expiry() {
+                crate::peripheral::Timer::set_compare(expires_at.ticks());
+            }
+            Self::sleep();
+        }
+    }
+
+    /// Called from the timer-compare interrupt vector: moves every expired
+    /// sleeper from the timer queue onto the run queue. Masked against
+    /// `register_timer`/`next_expiry` for the same reason as those.
+    pub fn on_timer_interrupt(&'static self, now: Clock) {
+        crate::critical_section::free(|| loop {
+            let head = self.timer_queue.get();
+            if head.is_null() {
+                break;
+            }
+            let expires_at = unsafe { (*head).expires_at.get() }.unwrap();
+            if expires_at > now {
+                break;
+            }
+            self.timer_queue.set(unsafe { (*head).timer_next.get() });
+            self.enqueue(head);
+        })
+    }
+
+    #[cfg(feature = "cortex-m-target")]
+    fn sleep() {
+        cortex_m::asm::wfe();
+    }
+
+    #[cfg(feature = "riscv-target")]
+    fn sleep() {
+        riscv::asm::wfi();
+    }
+}
+
+/// Registers `waker`'s task to be woken once `at` has passed. Called from a
+/// task's `poll` when it needs to sleep, e.g. from `Timer::after`.
+pub fn register_timer(at: Clock, waker: &Waker) {
+    let header = waker.as_raw().data() as *mut TaskHeader;
+    EXECUTOR.register_timer(header, at);
+}
+
+static WAKER_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(waker_clone, waker_wake, waker_wake_by_ref, waker_drop);
+
+unsafe fn waker_clone(p: *const ()) -> RawWaker {
+    RawWaker::new(p, &WAKER_VTABLE)
+}
+
+unsafe fn waker_wake(p: *const ()) {
+    EXECUTOR.enqueue(p as *mut TaskHeader);
+}
+
+unsafe fn waker_wake_by_ref(p: *const ()) {
+    EXECUTOR.enqueue(p as *mut TaskHeader);
+}
+
+unsafe fn waker_drop(_p: *const ()) {}
+
+fn make_waker(header: *const ()) -> Waker {
+    unsafe { Waker::from_raw(RawWaker::new(header, &WAKER_VTABLE)) }
+}
+
+/// Single-slot waker used to let a producer (an interrupt handler, another
+/// task) wake a specific sleeping task without going through the timer
+/// queue, e.g. "a byte arrived" or "a command was queued".
+///
+/// `register` runs in thread/poll context and `wake` can run from an
+/// interrupt handler (e.g. `UART_RX_WAKER` from `on_rx_interrupt`), so the
+/// slot is protected with an interrupt-masked critical section rather than
+/// a spinlock: a spinlock would deadlock if the interrupt preempted the
+/// thread mid-section and then spun waiting for a release that can only
+/// happen after the interrupt returns.
+pub struct AtomicWaker {
+    waker: Cell<Option<Waker>>,
+}
+
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+    pub const fn new() -> Self {
+        Self {
+            waker: Cell::new(None),
+        }
+    }
+
+    pub fn register(&self, waker: &Waker) {
+        crate::critical_section::free(|| self.waker.set(Some(waker.clone())));
+    }
+
+    pub fn wake(&self) {
+        let waker = crate::critical_section::free(|| self.waker.take());
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
@@ -10,78 +10,210 @@ use cortex_m_rt::entry;
 use riscv_rt::entry;
 
 use defmt_rtt as _;
-use heapless::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
 use heapless::String;
 use heapless::spsc::{Consumer, Producer, Queue};
-use fugit::{Duration, Instant};
+use fugit::Duration;
 
+mod critical_section;
+mod drivers;
+mod executor;
+mod net;
 mod peripheral;
 mod protocol;
 
+use drivers::ad7172::Ad7172;
+use drivers::pca9539::Pca9539;
+use executor::{AtomicWaker, TaskStorage, EXECUTOR};
+use net::{EthMac, NetServer};
+use peripheral::i2c::I2c;
+use peripheral::spi::Spi;
 use peripheral::{Uart, Gpio, Timer};
-use protocol::{Message, Command};
+use protocol::{Message, Command, FrameDecoder};
 
-const QUEUE_SIZE: usize = 16;
+/// Setup/filter slot the telemetry task's ADC channel is configured against.
+const ADC_SETUP: u8 = 0;
+/// Filter word for the default output data rate (SINC5+SINC1, no post-filter).
+const ADC_FILTER_WORD: u16 = 0x0500;
+
+/// Expander pins wired as outputs (pins 0..8: the two status LEDs plus six
+/// host-driven outputs); the rest are left as inputs. `Command::SetGpio`
+/// addresses the expander's 16 pins through pins 32..48.
+const EXPANDER_OUTPUT_MASK: u16 = 0x00FF;
+/// Command::SetGpio pins at or above this index address the expander
+/// instead of the MCU's own GPIO, as pin `n` - `EXPANDER_PIN_BASE`.
+const EXPANDER_PIN_BASE: u8 = 32;
+
+pub const QUEUE_SIZE: usize = 16;
 static mut COMMAND_QUEUE: Queue<Command, QUEUE_SIZE> = Queue::new();
 
+/// Outbound telemetry messages waiting to be broadcast over TCP, queued by
+/// `AdcTask` (which also writes each one straight to UART) and drained by
+/// `NetTask`.
+const MESSAGE_QUEUE_SIZE: usize = 8;
+static mut MESSAGE_QUEUE: Queue<Message, MESSAGE_QUEUE_SIZE> = Queue::new();
+
+/// Woken by `enqueue_command` whenever a command lands in `COMMAND_QUEUE`,
+/// so `CommandTask` can sleep between commands instead of polling.
+static COMMAND_WAKER: AtomicWaker = AtomicWaker::new();
+
+static HEARTBEAT_TASK: TaskStorage<HeartbeatTask> = TaskStorage::new();
+static COMMAND_TASK: TaskStorage<CommandTask> = TaskStorage::new();
+static UART_RX_TASK: TaskStorage<UartRxTask> = TaskStorage::new();
+static ADC_TASK: TaskStorage<AdcTask> = TaskStorage::new();
+static NET_TASK: TaskStorage<NetTask> = TaskStorage::new();
+
+/// Pushes `cmd` onto the command queue and wakes `CommandTask` if it's
+/// asleep. The counterpart `Producer` half is handed out for whichever
+/// source raises commands (e.g. a parsed host message).
+pub fn enqueue_command(producer: &mut Producer<'static, Command, QUEUE_SIZE>, cmd: Command) {
+    let _ = producer.enqueue(cmd);
+    COMMAND_WAKER.wake();
+}
+
 pub struct System {
     uart: Uart,
     gpio: Gpio,
-    timer: Timer,
+    spi: Spi,
+    command_producer: Producer<'static, Command, QUEUE_SIZE>,
     command_consumer: Consumer<'static, Command, QUEUE_SIZE>,
-    message_buffer: Vec<Message, 32>,
+    message_producer: Producer<'static, Message, MESSAGE_QUEUE_SIZE>,
+    message_consumer: Consumer<'static, Message, MESSAGE_QUEUE_SIZE>,
 }
 
 impl System {
     pub fn new() -> Self {
-        let (producer, consumer) = unsafe { COMMAND_QUEUE.split() };
-        
+        let (command_producer, command_consumer) = unsafe { COMMAND_QUEUE.split() };
+        let (message_producer, message_consumer) = unsafe { MESSAGE_QUEUE.split() };
+
         Self {
             uart: Uart::new(),
             gpio: Gpio::new(),
-            timer: Timer::new(),
-            command_consumer: consumer,
-            message_buffer: Vec::new(),
+            spi: Spi::new(),
+            command_producer,
+            command_consumer,
+            message_producer,
+            message_consumer,
         }
     }
 
+    /// `Timer`/`I2c` aren't kept as fields: nothing past `init()` needs an
+    /// owned handle, since every task reaches them through `Timer::now()`/
+    /// `I2c::new()` instead (cheap, repeatable, like `Uart::new()`).
     pub fn init(&mut self) {
         self.uart.init();
         self.gpio.init();
-        self.timer.init();
-        
+        Timer::new().init();
+        self.spi.init();
+        I2c::new().init();
+        Pca9539::new().init(EXPANDER_OUTPUT_MASK);
+
         defmt::info!("System initialized");
     }
 
-    pub fn run(&mut self) -> ! {
-        let mut last_tick = Instant::<u32, 1, 1000>::from_ticks(0);
-        
+    /// Spawns the heartbeat, command-handling, UART-draining, ADC telemetry
+    /// and TCP telemetry tasks and hands off to the executor; the core
+    /// sleeps in `WFE`/`wfi` whenever none of them have work, instead of
+    /// busy-polling.
+    pub fn run(self) -> ! {
+        let System {
+            uart,
+            gpio,
+            spi,
+            command_producer,
+            command_consumer,
+            message_producer,
+            message_consumer,
+        } = self;
+
+        let mut adc = Ad7172::new(spi);
+        adc.configure_channel(0, ADC_SETUP, ADC_FILTER_WORD);
+
+        let mut eth = EthMac::new();
+        eth.init();
+        let net_server = NetServer::new(eth);
+
+        EXECUTOR.spawn(HEARTBEAT_TASK.spawn(HeartbeatTask::new(gpio)));
+        EXECUTOR.spawn(COMMAND_TASK.spawn(CommandTask::new(uart, command_consumer)));
+        EXECUTOR.spawn(UART_RX_TASK.spawn(UartRxTask::new()));
+        EXECUTOR.spawn(ADC_TASK.spawn(AdcTask::new(adc, Uart::new(), message_producer)));
+        EXECUTOR.spawn(NET_TASK.spawn(NetTask::new(net_server, message_consumer, command_producer)));
+
+        EXECUTOR.run()
+    }
+}
+
+/// Toggles the status LED once a second via `Timer::after`, and mirrors the
+/// same blink onto the expander's first status LED via `Pca9539::service`
+/// (the second status LED is held off).
+struct HeartbeatTask {
+    gpio: Gpio,
+    expander: Pca9539,
+    sleep: Option<peripheral::TimerFuture>,
+}
+
+impl HeartbeatTask {
+    fn new(gpio: Gpio) -> Self {
+        Self {
+            gpio,
+            expander: Pca9539::new(),
+            sleep: None,
+        }
+    }
+}
+
+impl Future for HeartbeatTask {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
         loop {
-            if let Some(command) = self.command_consumer.dequeue() {
-                self.process_command(command);
-            }
-            
-            let current_tick = self.timer.get_tick();
-            if current_tick.duration_since(&last_tick) > Duration::<u32, 1, 1000>::from_ticks(1000) {
-                self.heartbeat();
-                last_tick = current_tick;
-            }
-            
-            if let Some(data) = self.uart.read() {
-                self.process_uart_data(data);
+            match &mut this.sleep {
+                None => {
+                    this.gpio.toggle_led();
+                    defmt::trace!("Heartbeat");
+                    let status = if this.gpio.led_on() { 0x0001 } else { 0x0000 };
+                    this.expander.service(status);
+                    this.sleep = Some(Timer::after(Duration::<u32, 1, 1000>::from_ticks(1000)));
+                }
+                Some(sleep) => {
+                    let sleep = unsafe { Pin::new_unchecked(sleep) };
+                    match sleep.poll(cx) {
+                        Poll::Ready(()) => this.sleep = None,
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
             }
         }
     }
+}
+
+/// Drains `COMMAND_QUEUE`, sleeping on `COMMAND_WAKER` between commands.
+struct CommandTask {
+    uart: Uart,
+    consumer: Consumer<'static, Command, QUEUE_SIZE>,
+}
+
+impl CommandTask {
+    fn new(uart: Uart, consumer: Consumer<'static, Command, QUEUE_SIZE>) -> Self {
+        Self { uart, consumer }
+    }
 
-    fn process_command(&mut self, cmd: Command) {
+    fn process(&mut self, cmd: Command) {
         match cmd {
             Command::SetGpio { pin, state } => {
-                self.gpio.set_pin(pin, state);
+                if pin >= EXPANDER_PIN_BASE {
+                    Pca9539::new().set_pin(pin - EXPANDER_PIN_BASE, state);
+                } else {
+                    Gpio::new().set_pin(pin, state);
+                }
                 defmt::info!("GPIO pin {} set to {}", pin, state);
             }
-            Command::SendMessage { data } => {
-                self.uart.write(&data);
-                defmt::info!("Sent message: {:?}", data);
+            Command::SendMessage { message } => {
+                self.uart.write(&message.serialize_framed());
+                defmt::info!("Sent message id={}, len={}", message.id, message.payload.len());
             }
             Command::Reset => {
                 defmt::info!("System reset requested");
@@ -89,14 +221,187 @@ impl System {
             }
         }
     }
+}
+
+impl Future for CommandTask {
+    type Output = ();
 
-    fn process_uart_data(&mut self, data: u8) {
-        defmt::trace!("UART data received: {}", data);
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        loop {
+            if let Some(cmd) = this.consumer.dequeue() {
+                this.process(cmd);
+                continue;
+            }
+            // Register before the final re-check to close the race where a
+            // command arrives between the dequeue above and registering.
+            COMMAND_WAKER.register(cx.waker());
+            if let Some(cmd) = this.consumer.dequeue() {
+                this.process(cmd);
+                continue;
+            }
+            return Poll::Pending;
+        }
     }
+}
 
-    fn heartbeat(&mut self) {
-        self.gpio.toggle_led();
-        defmt::trace!("Heartbeat");
+/// Drains bytes pushed into the UART RX ring by `Uart::on_rx_interrupt` and
+/// feeds them through a `FrameDecoder`, sleeping on
+/// `peripheral::UART_RX_WAKER` between bytes.
+struct UartRxTask {
+    rx: peripheral::ring_buffer::Reader<'static>,
+    decoder: FrameDecoder,
+}
+
+impl UartRxTask {
+    fn new() -> Self {
+        Self {
+            rx: Uart::rx_reader(),
+            decoder: FrameDecoder::new(),
+        }
+    }
+
+    fn feed(&mut self, byte: u8) {
+        match self.decoder.push_byte(byte) {
+            Ok(Some(message)) => {
+                defmt::info!("Received message id={}, len={}", message.id, message.payload.len());
+            }
+            Ok(None) => {}
+            Err(_) => defmt::warn!("Dropped malformed frame"),
+        }
+    }
+}
+
+impl Future for UartRxTask {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        loop {
+            if let Some(byte) = this.rx.pop() {
+                this.feed(byte);
+                continue;
+            }
+            peripheral::UART_RX_WAKER.register(cx.waker());
+            if let Some(byte) = this.rx.pop() {
+                this.feed(byte);
+                continue;
+            }
+            return Poll::Pending;
+        }
+    }
+}
+
+/// Samples the AD7172 once per period, writes each ready conversion
+/// straight to UART (id = channel, payload = big-endian 24-bit sample),
+/// and queues a copy on `MESSAGE_QUEUE` for `NetTask` to broadcast over TCP.
+struct AdcTask {
+    adc: Ad7172<Spi>,
+    uart: Uart,
+    message_producer: Producer<'static, Message, MESSAGE_QUEUE_SIZE>,
+    sleep: Option<peripheral::TimerFuture>,
+}
+
+impl AdcTask {
+    fn new(
+        adc: Ad7172<Spi>,
+        uart: Uart,
+        message_producer: Producer<'static, Message, MESSAGE_QUEUE_SIZE>,
+    ) -> Self {
+        Self {
+            adc,
+            uart,
+            message_producer,
+            sleep: None,
+        }
+    }
+
+    fn sample(&mut self) {
+        if let Some((channel, sample)) = self.adc.read_data() {
+            let mut message = Message::new(channel as u16);
+            let _ = message.add_data(&sample.to_be_bytes()[1..]);
+            self.uart.write(&message.serialize_framed());
+            let _ = self.message_producer.enqueue(message);
+        }
+    }
+}
+
+impl Future for AdcTask {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.sleep {
+                None => {
+                    this.sample();
+                    this.sleep = Some(Timer::after(Duration::<u32, 1, 1000>::from_ticks(200)));
+                }
+                Some(sleep) => {
+                    let sleep = unsafe { Pin::new_unchecked(sleep) };
+                    match sleep.poll(cx) {
+                        Poll::Ready(()) => this.sleep = None,
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Polls the smoltcp interface at a fixed cadence: drives the TCP socket,
+/// decodes inbound bytes into `COMMAND_QUEUE` commands, and sends one
+/// queued `MESSAGE_QUEUE` entry out over the socket if there's room.
+///
+/// Note: `NetServer::poll` only ever turns decoded inbound frames into
+/// `Command::SendMessage` — a TCP client can't drive `SetGpio` or
+/// `Reset` this way, only have messages written back out over UART.
+struct NetTask {
+    server: NetServer,
+    message_consumer: Consumer<'static, Message, MESSAGE_QUEUE_SIZE>,
+    command_producer: Producer<'static, Command, QUEUE_SIZE>,
+    sleep: Option<peripheral::TimerFuture>,
+}
+
+impl NetTask {
+    fn new(
+        server: NetServer,
+        message_consumer: Consumer<'static, Message, MESSAGE_QUEUE_SIZE>,
+        command_producer: Producer<'static, Command, QUEUE_SIZE>,
+    ) -> Self {
+        Self {
+            server,
+            message_consumer,
+            command_producer,
+            sleep: None,
+        }
+    }
+
+    fn tick(&mut self) {
+        self.server.poll(&mut self.message_consumer, &mut self.command_producer);
+    }
+}
+
+impl Future for NetTask {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.sleep {
+                None => {
+                    this.tick();
+                    this.sleep = Some(Timer::after(Duration::<u32, 1, 1000>::from_ticks(50)));
+                }
+                Some(sleep) => {
+                    let sleep = unsafe { Pin::new_unchecked(sleep) };
+                    match sleep.poll(cx) {
+                        Poll::Ready(()) => this.sleep = None,
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -105,4 +410,4 @@ fn main() -> ! {
     let mut system = System::new();
     system.init();
     system.run()
-}
\ No newline at end of file
+}
@@ -0,0 +1,203 @@
+//! smoltcp-based TCP telemetry server, running alongside the UART path.
+//!
+//! Brings up a `smoltcp` interface over the `EthMac` peripheral and serves
+//! one TCP socket: every `Message` queued for broadcast goes out over the
+//! socket the same way it goes out over UART (`Message::serialize_framed`),
+//! and inbound bytes are parsed with the same `FrameDecoder` and pushed
+//! onto `COMMAND_QUEUE`, so the device can be driven without a serial
+//! cable. Socket storage is static and heapless-backed to stay `no_std`/
+//! no-alloc alongside the rest of the firmware.
+
+use heapless::spsc::{Consumer, Producer};
+use smoltcp::iface::{Config, Interface, SocketHandle, SocketSet, SocketStorage};
+use smoltcp::phy::{Device, DeviceCapabilities, Medium, RxToken, TxToken};
+use smoltcp::socket::tcp;
+use smoltcp::time::Instant as SmolInstant;
+use smoltcp::wire::{EthernetAddress, HardwareAddress, IpAddress, IpCidr, Ipv4Address};
+
+use crate::protocol::{Command, FrameDecoder, Message};
+use crate::peripheral::Timer;
+
+const MTU: usize = 1514;
+const TCP_PORT: u16 = 7171;
+const TCP_BUF_LEN: usize = 512;
+
+const DEVICE_MAC: EthernetAddress = EthernetAddress([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+const DEVICE_IP: Ipv4Address = Ipv4Address::new(192, 168, 1, 50);
+const DEVICE_PREFIX: u8 = 24;
+
+const MAC_DATA: *mut u8 = 0x4000_8000 as *mut u8;
+const MAC_STATUS: *const u32 = 0x4000_8004 as *const u32;
+const MAC_LEN: *mut u32 = 0x4000_8008 as *mut u32;
+const MAC_STATUS_RX_READY: u32 = 0x01;
+const MAC_STATUS_TX_READY: u32 = 0x02;
+
+static mut TCP_RX_STORAGE: [u8; TCP_BUF_LEN] = [0; TCP_BUF_LEN];
+static mut TCP_TX_STORAGE: [u8; TCP_BUF_LEN] = [0; TCP_BUF_LEN];
+static mut SOCKET_STORAGE: [SocketStorage; 1] = [SocketStorage::EMPTY];
+
+/// A single-frame-at-a-time MMIO Ethernet MAC: a frame is ready to read
+/// when the status register's RX-ready bit is set, and one can be sent
+/// when its TX-ready bit is set.
+pub struct EthMac;
+
+impl EthMac {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn init(&mut self) {
+        defmt::debug!("Ethernet MAC initialized");
+    }
+}
+
+pub struct EthRxToken {
+    frame: heapless::Vec<u8, MTU>,
+}
+
+impl RxToken for EthRxToken {
+    fn consume<R, F: FnOnce(&mut [u8]) -> R>(mut self, f: F) -> R {
+        f(&mut self.frame)
+    }
+}
+
+pub struct EthTxToken;
+
+impl TxToken for EthTxToken {
+    fn consume<R, F: FnOnce(&mut [u8]) -> R>(self, len: usize, f: F) -> R {
+        let mut frame: heapless::Vec<u8, MTU> = heapless::Vec::new();
+        let _ = frame.resize(len, 0);
+        let result = f(&mut frame);
+        unsafe {
+            core::ptr::write_volatile(MAC_LEN, len as u32);
+            for &byte in &frame {
+                core::ptr::write_volatile(MAC_DATA, byte);
+            }
+        }
+        result
+    }
+}
+
+impl Device for EthMac {
+    type RxToken<'a> = EthRxToken;
+    type TxToken<'a> = EthTxToken;
+
+    fn receive(
+        &mut self,
+        _timestamp: SmolInstant,
+    ) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        let status = unsafe { core::ptr::read_volatile(MAC_STATUS) };
+        if status & MAC_STATUS_RX_READY == 0 {
+            return None;
+        }
+        let len = (unsafe { core::ptr::read_volatile(MAC_LEN) } as usize).min(MTU);
+        let mut frame: heapless::Vec<u8, MTU> = heapless::Vec::new();
+        for _ in 0..len {
+            let _ = frame.push(unsafe { core::ptr::read_volatile(MAC_DATA) });
+        }
+        Some((EthRxToken { frame }, EthTxToken))
+    }
+
+    fn transmit(&mut self, _timestamp: SmolInstant) -> Option<Self::TxToken<'_>> {
+        let status = unsafe { core::ptr::read_volatile(MAC_STATUS) };
+        if status & MAC_STATUS_TX_READY == 0 {
+            return None;
+        }
+        Some(EthTxToken)
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut caps = DeviceCapabilities::default();
+        caps.max_transmission_unit = MTU;
+        caps.medium = Medium::Ethernet;
+        caps
+    }
+}
+
+/// Owns the interface, socket set and framing state for the TCP telemetry
+/// server. `poll()` is meant to be called periodically from an executor
+/// task (see `NetTask` in `main.rs`).
+pub struct NetServer {
+    device: EthMac,
+    iface: Interface,
+    sockets: SocketSet<'static>,
+    tcp_handle: SocketHandle,
+    decoder: FrameDecoder,
+}
+
+impl NetServer {
+    pub fn new(mut device: EthMac) -> Self {
+        let mut config = Config::new(HardwareAddress::Ethernet(DEVICE_MAC));
+        // Seeded from the free-running tick counter at bring-up time rather
+        // than a fixed value, so the initial sequence number isn't the same
+        // on every boot.
+        config.random_seed = Timer::now().ticks() as u64;
+
+        let now = SmolInstant::from_millis(Timer::now().ticks() as i64);
+        let mut iface = Interface::new(config, &mut device, now);
+        iface.update_ip_addrs(|addrs| {
+            let _ = addrs.push(IpCidr::new(IpAddress::Ipv4(DEVICE_IP), DEVICE_PREFIX));
+        });
+
+        let mut sockets = SocketSet::new(unsafe { &mut SOCKET_STORAGE[..] });
+        let tcp_socket = tcp::Socket::new(
+            tcp::SocketBuffer::new(unsafe { &mut TCP_RX_STORAGE[..] }),
+            tcp::SocketBuffer::new(unsafe { &mut TCP_TX_STORAGE[..] }),
+        );
+        let tcp_handle = sockets.add(tcp_socket);
+
+        Self {
+            device,
+            iface,
+            sockets,
+            tcp_handle,
+            decoder: FrameDecoder::new(),
+        }
+    }
+
+    /// Drives the interface, listens for a client if nothing is connected,
+    /// decodes inbound bytes into `Command`s via `command_producer`, and
+    /// sends one queued outbound `Message` if the socket has room for the
+    /// *whole* framed message. Peeks rather than dequeuing so a message
+    /// isn't lost — or sent truncated — while no client is connected or
+    /// the send buffer doesn't have room for it yet; it's only dequeued
+    /// once it's actually gone out (or is permanently too big to fit).
+    pub fn poll(
+        &mut self,
+        message_consumer: &mut Consumer<'static, Message, { crate::MESSAGE_QUEUE_SIZE }>,
+        command_producer: &mut Producer<'static, Command, { crate::QUEUE_SIZE }>,
+    ) {
+        let now = SmolInstant::from_millis(Timer::now().ticks() as i64);
+        self.iface.poll(now, &mut self.device, &mut self.sockets);
+
+        let decoder = &mut self.decoder;
+        let socket = self.sockets.get_mut::<tcp::Socket>(self.tcp_handle);
+        if !socket.is_open() {
+            let _ = socket.listen(TCP_PORT);
+        }
+
+        if socket.can_recv() {
+            let _ = socket.recv(|data| {
+                for &byte in data {
+                    if let Ok(Some(message)) = decoder.push_byte(byte) {
+                        crate::enqueue_command(command_producer, Command::SendMessage { message });
+                    }
+                }
+                (data.len(), ())
+            });
+        }
+
+        if socket.can_send() {
+            if let Some(message) = message_consumer.peek() {
+                let frame = message.serialize_framed();
+                if frame.len() > socket.send_capacity() {
+                    defmt::warn!("Framed message larger than TCP send buffer, dropping");
+                    message_consumer.dequeue();
+                } else if socket.send_capacity() - socket.send_queue() >= frame.len() {
+                    let _ = socket.send_slice(&frame);
+                    message_consumer.dequeue();
+                }
+            }
+        }
+    }
+}
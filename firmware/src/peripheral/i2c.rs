@@ -0,0 +1,42 @@
+const I2C_BASE: *mut u32 = 0x4000_A000 as *mut u32;
+const I2C_STATUS_DONE: u32 = 0x01;
+
+/// MMIO I2C peripheral: each byte is addressed, written or read, and
+/// acknowledged through the status register's "done" bit.
+pub struct I2c;
+
+impl I2c {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn init(&mut self) {
+        unsafe { core::ptr::write_volatile(I2C_BASE.offset(0), 0x0000_0001) };
+        defmt::debug!("I2C initialized");
+    }
+
+    /// Writes `data` to `addr` in a single transaction (e.g. a register
+    /// address byte followed by its value).
+    pub fn write(&mut self, addr: u8, data: &[u8]) {
+        unsafe {
+            core::ptr::write_volatile(I2C_BASE.offset(2) as *mut u8, addr << 1);
+            for &byte in data {
+                core::ptr::write_volatile(I2C_BASE.offset(3) as *mut u8, byte);
+                while core::ptr::read_volatile(I2C_BASE.offset(1)) & I2C_STATUS_DONE == 0 {}
+            }
+        }
+    }
+
+    /// Writes `reg` then reads `buf.len()` bytes back from `addr` (a
+    /// combined write-then-read register access).
+    pub fn write_read(&mut self, addr: u8, reg: u8, buf: &mut [u8]) {
+        self.write(addr, &[reg]);
+        unsafe {
+            core::ptr::write_volatile(I2C_BASE.offset(2) as *mut u8, (addr << 1) | 1);
+            for byte in buf.iter_mut() {
+                while core::ptr::read_volatile(I2C_BASE.offset(1)) & I2C_STATUS_DONE == 0 {}
+                *byte = core::ptr::read_volatile(I2C_BASE.offset(3) as *const u8);
+            }
+        }
+    }
+}
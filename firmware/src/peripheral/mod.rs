@@ -0,0 +1,356 @@
+pub mod i2c;
+pub mod ring_buffer;
+pub mod spi;
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use fugit::{Duration, Instant};
+
+use crate::executor::{self, AtomicWaker, Clock};
+use ring_buffer::{Reader, RingBuffer, Writer};
+
+/// Woken by `Uart::on_rx_interrupt` whenever a byte lands in `RX_RING`, so
+/// an async RX-draining task can sleep between bytes instead of polling.
+pub static UART_RX_WAKER: AtomicWaker = AtomicWaker::new();
+
+const UART_DATA: *mut u8 = 0x4000_4400 as *mut u8;
+const UART_STATUS: *const u32 = 0x4000_4404 as *const u32;
+const UART_STATUS_RX_READY: u32 = 0x01;
+const UART_STATUS_TX_READY: u32 = 0x02;
+const UART_CTRL: *mut u32 = 0x4000_4408 as *mut u32;
+const UART_CTRL_ENABLE: u32 = 0x0000_0001;
+const UART_CTRL_RX_IRQ_ENABLE: u32 = 0x0000_0002;
+const UART_CTRL_TX_IRQ_ENABLE: u32 = 0x0000_0004;
+
+const RX_BUF_LEN: usize = 256;
+const TX_BUF_LEN: usize = 256;
+
+static RX_RING: RingBuffer = RingBuffer::new();
+static TX_RING: RingBuffer = RingBuffer::new();
+static mut RX_BUF: [u8; RX_BUF_LEN] = [0; RX_BUF_LEN];
+static mut TX_BUF: [u8; TX_BUF_LEN] = [0; TX_BUF_LEN];
+
+pub struct Uart {
+    rx: Reader<'static>,
+    tx: Writer<'static>,
+}
+
+impl Uart {
+    /// Builds a handle onto the shared RX/TX rings. Cheap and repeatable —
+    /// call it again anywhere a task needs its own handle; the rings
+    /// themselves are only reset by `init()`.
+    pub fn new() -> Self {
+        Self {
+            rx: RX_RING.reader(),
+            tx: TX_RING.writer(),
+        }
+    }
+
+    /// A read-only handle for a task that only ever drains received bytes.
+    pub fn rx_reader() -> Reader<'static> {
+        RX_RING.reader()
+    }
+
+    /// Resets the rings, enables the UART and its RX/TX interrupts at the
+    /// peripheral, and unmasks the vector. Without this, `on_rx_interrupt`/
+    /// `on_tx_interrupt` are never called, the RX ring never fills, and
+    /// `UartRxTask` sleeps on `UART_RX_WAKER` forever.
+    pub fn init(&mut self) {
+        unsafe {
+            RX_RING.init(RX_BUF.as_mut_ptr(), RX_BUF_LEN);
+            TX_RING.init(TX_BUF.as_mut_ptr(), TX_BUF_LEN);
+            core::ptr::write_volatile(
+                UART_CTRL,
+                UART_CTRL_ENABLE | UART_CTRL_RX_IRQ_ENABLE | UART_CTRL_TX_IRQ_ENABLE,
+            );
+        }
+        uart_vector::unmask();
+        defmt::debug!("UART initialized");
+    }
+
+    pub fn deinit(&mut self) {
+        RX_RING.deinit();
+        TX_RING.deinit();
+    }
+
+    /// Queues `data` for transmission and kicks the transmitter if it's
+    /// currently idle; the rest drains out via `on_tx_interrupt`.
+    pub fn write(&mut self, data: &[u8]) {
+        for &byte in data {
+            if self.tx.push(byte).is_err() {
+                defmt::warn!("UART TX ring full, dropping byte");
+            }
+        }
+        Self::kick_tx();
+    }
+
+    /// Dequeues a byte received by `on_rx_interrupt`, if any is buffered.
+    pub fn read(&mut self) -> Option<u8> {
+        self.rx.pop()
+    }
+
+    /// Primes transmission if the hardware is idle. Masks interrupts for
+    /// the pop-and-write so it can't race `on_tx_interrupt`, which must
+    /// otherwise be the ring's sole consumer.
+    fn kick_tx() {
+        crate::critical_section::free(|| {
+            let status = unsafe { core::ptr::read_volatile(UART_STATUS) };
+            if status & UART_STATUS_TX_READY != 0 {
+                if let Some(byte) = TX_RING.reader().pop() {
+                    unsafe { core::ptr::write_volatile(UART_DATA, byte) };
+                }
+            }
+        });
+    }
+
+    /// Called from the UART RX interrupt vector: pulls the byte the
+    /// hardware just latched into the ring buffer for `read()` to drain.
+    pub fn on_rx_interrupt() {
+        let status = unsafe { core::ptr::read_volatile(UART_STATUS) };
+        if status & UART_STATUS_RX_READY != 0 {
+            let byte = unsafe { core::ptr::read_volatile(UART_DATA) };
+            if RX_RING.writer().push(byte).is_err() {
+                defmt::warn!("UART RX ring full, dropping byte");
+            }
+            UART_RX_WAKER.wake();
+        }
+    }
+
+    /// Called from the UART TX interrupt vector once the last byte has
+    /// shifted out: feeds the next queued byte, if any.
+    pub fn on_tx_interrupt() {
+        if let Some(byte) = TX_RING.reader().pop() {
+            unsafe { core::ptr::write_volatile(UART_DATA, byte) };
+        }
+    }
+}
+
+/// Wires the UART interrupt to `Uart::on_rx_interrupt`/`on_tx_interrupt`.
+#[cfg(feature = "cortex-m-target")]
+mod uart_vector {
+    use cortex_m_rt::interrupt;
+
+    /// Stand-in for a PAC's `Interrupt` enum, same rationale as
+    /// `timer_vector::Interrupt`.
+    #[derive(Clone, Copy)]
+    enum Interrupt {
+        Uart0 = 1,
+    }
+
+    unsafe impl cortex_m_rt::InterruptNumber for Interrupt {
+        #[inline(always)]
+        fn number(self) -> u16 {
+            self as u16
+        }
+    }
+
+    #[interrupt]
+    fn Uart0() {
+        super::Uart::on_rx_interrupt();
+        super::Uart::on_tx_interrupt();
+    }
+
+    pub fn unmask() {
+        unsafe { cortex_m::peripheral::NVIC::unmask(Interrupt::Uart0) };
+    }
+}
+
+#[cfg(feature = "riscv-target")]
+mod uart_vector {
+    use riscv_rt::interrupt;
+
+    /// The PLIC source for the UART interrupt is board-specific; this
+    /// enables machine-mode interrupts globally so the `#[interrupt]`-
+    /// attributed `Uart0` handler below can fire.
+    #[interrupt]
+    fn Uart0() {
+        super::Uart::on_rx_interrupt();
+        super::Uart::on_tx_interrupt();
+    }
+
+    pub fn unmask() {
+        unsafe { riscv::interrupt::enable() };
+    }
+}
+
+pub struct Gpio {
+    led_state: bool,
+}
+
+impl Gpio {
+    pub fn new() -> Self {
+        Self { led_state: false }
+    }
+
+    pub fn init(&mut self) {
+        unsafe {
+            let gpio_base = 0x4002_0000 as *mut u32;
+            core::ptr::write_volatile(gpio_base.offset(0), 0x0000_0001);
+        }
+        defmt::debug!("GPIO initialized");
+    }
+
+    pub fn set_pin(&mut self, pin: u8, state: bool) {
+        unsafe {
+            let gpio_base = 0x4002_0000 as *mut u32;
+            let current = core::ptr::read_volatile(gpio_base.offset(1));
+            if state {
+                core::ptr::write_volatile(gpio_base.offset(1), current | (1 << pin));
+            } else {
+                core::ptr::write_volatile(gpio_base.offset(1), current & !(1 << pin));
+            }
+        }
+    }
+
+    pub fn toggle_led(&mut self) {
+        self.led_state = !self.led_state;
+        self.set_pin(13, self.led_state);
+    }
+
+    pub fn led_on(&self) -> bool {
+        self.led_state
+    }
+
+    pub fn read_pin(&mut self, pin: u8) -> bool {
+        unsafe {
+            let gpio_base = 0x4002_0000 as *const u32;
+            let value = core::ptr::read_volatile(gpio_base.offset(2));
+            value & (1 << pin) != 0
+        }
+    }
+}
+
+const TIM_BASE: *mut u32 = 0x4000_0000 as *mut u32;
+const TIM_COUNTER: *const u32 = 0x4000_0004 as *const u32;
+const TIM_COMPARE_OFFSET: isize = 2;
+const TIM_CTRL_ENABLE: u32 = 0x0000_0001;
+const TIM_CTRL_COMPARE_IRQ_ENABLE: u32 = 0x0000_0002;
+
+pub struct Timer {
+    counter: u32,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self { counter: 0 }
+    }
+
+    /// Enables the counter, enables the compare-match interrupt at the
+    /// peripheral, and unmasks the vector so a sleeping `Executor::run`
+    /// actually gets woken by `set_compare`'s deadline (see `Timer0`).
+    pub fn init(&mut self) {
+        unsafe {
+            core::ptr::write_volatile(
+                TIM_BASE.offset(0),
+                TIM_CTRL_ENABLE | TIM_CTRL_COMPARE_IRQ_ENABLE,
+            );
+        }
+        timer_vector::unmask();
+        defmt::debug!("Timer initialized");
+    }
+
+    pub fn get_tick(&mut self) -> Clock {
+        self.counter = unsafe { core::ptr::read_volatile(TIM_COUNTER) };
+        Instant::from_ticks(self.counter)
+    }
+
+    /// Current tick, read straight off the hardware counter. Used by
+    /// `after()` and the executor, neither of which own a `Timer` instance.
+    pub fn now() -> Clock {
+        Instant::from_ticks(unsafe { core::ptr::read_volatile(TIM_COUNTER) })
+    }
+
+    /// Programs the compare register the executor's timer interrupt fires
+    /// against when the nearest sleeping task is due to wake.
+    pub fn set_compare(ticks: u32) {
+        unsafe { core::ptr::write_volatile(TIM_BASE.offset(TIM_COMPARE_OFFSET), ticks) };
+    }
+
+    /// Async sleep: resolves once at least `duration` has elapsed.
+    pub fn after(duration: Duration<u32, 1, 1000>) -> TimerFuture {
+        TimerFuture {
+            expires_at: Self::now() + duration,
+            registered: false,
+        }
+    }
+
+    /// Called from the timer-compare interrupt vector: wakes every task
+    /// whose `after()` has elapsed.
+    pub fn on_timer_interrupt() {
+        executor::EXECUTOR.on_timer_interrupt(Self::now());
+    }
+}
+
+/// Wires the timer-compare interrupt to `Timer::on_timer_interrupt`. Without
+/// this, nothing ever calls it, the core sleeps through `set_compare`'s
+/// deadline in `Executor::run`, and every `Timer::after` future hangs
+/// forever.
+#[cfg(feature = "cortex-m-target")]
+mod timer_vector {
+    use cortex_m_rt::interrupt;
+
+    /// Stand-in for a PAC's `Interrupt` enum: `cortex-m-rt`'s `#[interrupt]`
+    /// only needs an `InterruptNumber` impl in scope, not a full
+    /// SVD-generated PAC.
+    #[derive(Clone, Copy)]
+    enum Interrupt {
+        Timer0 = 0,
+    }
+
+    unsafe impl cortex_m_rt::InterruptNumber for Interrupt {
+        #[inline(always)]
+        fn number(self) -> u16 {
+            self as u16
+        }
+    }
+
+    #[interrupt]
+    fn Timer0() {
+        super::Timer::on_timer_interrupt();
+    }
+
+    pub fn unmask() {
+        unsafe { cortex_m::peripheral::NVIC::unmask(Interrupt::Timer0) };
+    }
+}
+
+#[cfg(feature = "riscv-target")]
+mod timer_vector {
+    use riscv_rt::interrupt;
+
+    /// The PLIC source for the timer-compare interrupt is board-specific;
+    /// this enables machine-mode interrupts globally so the
+    /// `#[interrupt]`-attributed `MachineTimer` handler below can fire.
+    #[interrupt]
+    fn MachineTimer() {
+        super::Timer::on_timer_interrupt();
+    }
+
+    pub fn unmask() {
+        unsafe { riscv::interrupt::enable() };
+    }
+}
+
+/// The future returned by `Timer::after`.
+pub struct TimerFuture {
+    expires_at: Clock,
+    registered: bool,
+}
+
+impl Future for TimerFuture {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if Timer::now() >= this.expires_at {
+            return Poll::Ready(());
+        }
+        if !this.registered {
+            this.registered = true;
+            executor::register_timer(this.expires_at, cx.waker());
+        }
+        Poll::Pending
+    }
+}
\ No newline at end of file
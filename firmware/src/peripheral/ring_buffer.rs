@@ -0,0 +1,132 @@
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// Single-producer/single-consumer ring buffer backed by donated memory.
+///
+/// All operations take `&self` so the buffer can live in a `static` and be
+/// shared between an interrupt handler (the producer or the consumer,
+/// depending on direction) and the main loop (the other side) without a
+/// lock. This is only sound with exactly one producer and one consumer at a
+/// time, which is why access is handed out through the separate [`Reader`]
+/// and [`Writer`] halves rather than directly on `RingBuffer`.
+pub struct RingBuffer {
+    buf: AtomicPtr<u8>,
+    len: AtomicUsize,
+    start: AtomicUsize,
+    end: AtomicUsize,
+}
+
+impl RingBuffer {
+    pub const fn new() -> Self {
+        Self {
+            buf: AtomicPtr::new(core::ptr::null_mut()),
+            len: AtomicUsize::new(0),
+            start: AtomicUsize::new(0),
+            end: AtomicUsize::new(0),
+        }
+    }
+
+    /// Donate `len` bytes starting at `buf` as backing storage and reset
+    /// the buffer to empty. Must be called before the first `reader()`/
+    /// `writer()` use, typically once at startup.
+    pub fn init(&self, buf: *mut u8, len: usize) {
+        self.start.store(0, Ordering::Relaxed);
+        self.end.store(0, Ordering::Relaxed);
+        self.len.store(len, Ordering::Relaxed);
+        self.buf.store(buf, Ordering::Relaxed);
+    }
+
+    /// Reclaim the backing storage. The buffer must not be read or written
+    /// again until a subsequent `init()`.
+    pub fn deinit(&self) {
+        self.buf.store(core::ptr::null_mut(), Ordering::Relaxed);
+        self.len.store(0, Ordering::Relaxed);
+        self.start.store(0, Ordering::Relaxed);
+        self.end.store(0, Ordering::Relaxed);
+    }
+
+    /// Consumer half. Hand this to whichever side dequeues bytes.
+    pub fn reader(&'static self) -> Reader<'static> {
+        Reader { ring: self }
+    }
+
+    /// Producer half. Hand this to whichever side enqueues bytes.
+    pub fn writer(&'static self) -> Writer<'static> {
+        Writer { ring: self }
+    }
+
+    /// Both halves at once, for the common case of one owner per end.
+    pub fn split(&'static self) -> (Reader<'static>, Writer<'static>) {
+        (self.reader(), self.writer())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start.load(Ordering::Relaxed) == self.end.load(Ordering::Relaxed)
+    }
+
+    fn is_full(&self) -> bool {
+        let len = self.len.load(Ordering::Relaxed);
+        if len == 0 {
+            return true;
+        }
+        let end = self.end.load(Ordering::Relaxed);
+        Self::wrap(end + 1, len) == self.start.load(Ordering::Relaxed)
+    }
+
+    fn wrap(idx: usize, len: usize) -> usize {
+        if idx >= len {
+            idx - len
+        } else {
+            idx
+        }
+    }
+}
+
+unsafe impl Sync for RingBuffer {}
+
+/// The consumer half of a [`RingBuffer`]. Safe for exactly one consumer.
+pub struct Reader<'a> {
+    ring: &'a RingBuffer,
+}
+
+impl<'a> Reader<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    pub fn pop(&self) -> Option<u8> {
+        if self.ring.is_empty() {
+            return None;
+        }
+        let len = self.ring.len.load(Ordering::Relaxed);
+        let start = self.ring.start.load(Ordering::Relaxed);
+        let ptr = self.ring.buf.load(Ordering::Relaxed);
+        let byte = unsafe { ptr.add(start).read_volatile() };
+        self.ring.start.store(RingBuffer::wrap(start + 1, len), Ordering::Relaxed);
+        Some(byte)
+    }
+}
+
+/// The producer half of a [`RingBuffer`]. Safe for exactly one producer.
+pub struct Writer<'a> {
+    ring: &'a RingBuffer,
+}
+
+impl<'a> Writer<'a> {
+    pub fn is_full(&self) -> bool {
+        self.ring.is_full()
+    }
+
+    /// Pushes `byte`, returning it back on a full buffer instead of
+    /// overwriting unread data.
+    pub fn push(&self, byte: u8) -> Result<(), u8> {
+        if self.ring.is_full() {
+            return Err(byte);
+        }
+        let len = self.ring.len.load(Ordering::Relaxed);
+        let end = self.ring.end.load(Ordering::Relaxed);
+        let ptr = self.ring.buf.load(Ordering::Relaxed);
+        unsafe { ptr.add(end).write_volatile(byte) };
+        self.ring.end.store(RingBuffer::wrap(end + 1, len), Ordering::Relaxed);
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+use super::Gpio;
+
+/// A byte-at-a-time SPI bus, implemented either by the `Spi` MMIO
+/// peripheral or by `SoftSpi` bit-banging over GPIO. Drivers (e.g. the
+/// AD7172) are written against this trait so they work on either.
+pub trait SpiBus {
+    /// Shifts `byte` out while shifting a byte in, and returns it.
+    fn transfer(&mut self, byte: u8) -> u8;
+}
+
+const SPI_BASE: *mut u32 = 0x4000_6000 as *mut u32;
+const SPI_STATUS_DONE: u32 = 0x01;
+
+/// Hardware SPI peripheral (MMIO): write a byte to the data register,
+/// wait for the status register's done bit to be set, read the shifted-in
+/// byte back out.
+pub struct Spi;
+
+impl Spi {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn init(&mut self) {
+        unsafe { core::ptr::write_volatile(SPI_BASE.offset(0), 0x0000_0001) };
+        defmt::debug!("SPI initialized");
+    }
+}
+
+impl SpiBus for Spi {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        unsafe {
+            core::ptr::write_volatile(SPI_BASE.offset(2) as *mut u8, byte);
+            while core::ptr::read_volatile(SPI_BASE.offset(1)) & SPI_STATUS_DONE == 0 {}
+            core::ptr::read_volatile(SPI_BASE.offset(2) as *const u8)
+        }
+    }
+}
+
+/// Bit-banged SPI mode 3 (CPOL=1, CPHA=1), MSB first, over plain GPIO
+/// pins — a fallback for boards without a hardware SPI block.
+pub struct SoftSpi {
+    sclk: u8,
+    mosi: u8,
+    miso: u8,
+    cs: u8,
+    gpio: Gpio,
+}
+
+impl SoftSpi {
+    pub fn new(sclk: u8, mosi: u8, miso: u8, cs: u8) -> Self {
+        Self {
+            sclk,
+            mosi,
+            miso,
+            cs,
+            gpio: Gpio::new(),
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.gpio.set_pin(self.cs, true);
+        self.gpio.set_pin(self.sclk, true);
+    }
+
+    pub fn select(&mut self) {
+        self.gpio.set_pin(self.cs, false);
+    }
+
+    pub fn deselect(&mut self) {
+        self.gpio.set_pin(self.cs, true);
+    }
+}
+
+impl SpiBus for SoftSpi {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        let mut result = 0u8;
+        for i in (0..8).rev() {
+            self.gpio.set_pin(self.sclk, false);
+            self.gpio.set_pin(self.mosi, (byte >> i) & 1 != 0);
+            let bit_in = self.gpio.read_pin(self.miso);
+            result = (result << 1) | (bit_in as u8);
+            self.gpio.set_pin(self.sclk, true);
+        }
+        result
+    }
+}
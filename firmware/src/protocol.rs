@@ -1,5 +1,13 @@
 use heapless::Vec;
 
+/// `id(2) + len(1) + payload(64) + crc(2)`, rounded up like the existing
+/// 128-byte `serialize()` buffer.
+const RAW_MAX: usize = 128;
+
+/// Worst-case COBS overhead on top of `RAW_MAX` (one extra block byte per
+/// 254 bytes) plus the trailing `0x00` delimiter.
+const FRAME_MAX: usize = 160;
+
 #[derive(Debug, Clone)]
 pub struct Message {
     pub id: u16,
@@ -9,10 +17,25 @@ pub struct Message {
 #[derive(Debug)]
 pub enum Command {
     SetGpio { pin: u8, state: bool },
-    SendMessage { data: Vec<u8, 128> },
+    SendMessage { message: Message },
     Reset,
 }
 
+/// Errors a [`FrameDecoder`] can report for a terminated frame.
+#[derive(Debug)]
+pub enum FrameError {
+    /// More bytes arrived before a `0x00` terminator than the decoder buffers,
+    /// or the frame decoded to more payload than a `Message` holds.
+    Overrun,
+    /// The COBS-encoded bytes don't form a valid sequence of blocks.
+    BadCobs,
+    /// The frame decoded but its CRC-16 didn't match.
+    BadCrc,
+    /// The frame decoded shorter than the minimum `id + len` header plus
+    /// CRC, or its length byte doesn't match the payload it carries.
+    Truncated,
+}
+
 impl Message {
     pub fn new(id: u16) -> Self {
         Self {
@@ -33,4 +56,157 @@ impl Message {
         let _ = buffer.extend_from_slice(&self.payload);
         buffer
     }
+
+    /// `serialize()` plus a trailing CRC-16/CCITT over its bytes (`id`, the
+    /// length byte and the payload), COBS-encoded and terminated with a `0x00` delimiter so a
+    /// receiver can always resync on the next zero byte even after a
+    /// dropped UART byte desynchronizes it.
+    pub fn serialize_framed(&self) -> Vec<u8, FRAME_MAX> {
+        let raw = self.serialize();
+        let crc = crc16_ccitt(&raw);
+
+        let mut with_crc: Vec<u8, RAW_MAX> = Vec::new();
+        let _ = with_crc.extend_from_slice(&raw);
+        let _ = with_crc.push((crc >> 8) as u8);
+        let _ = with_crc.push(crc as u8);
+
+        let mut frame: Vec<u8, FRAME_MAX> = Vec::new();
+        let _ = cobs_encode(&with_crc, &mut frame);
+        let _ = frame.push(0x00);
+        frame
+    }
+}
+
+/// Streaming counterpart to `serialize_framed`: feed it bytes one at a time
+/// (e.g. straight from the UART RX ring buffer) and it yields a `Message`
+/// each time a `0x00`-terminated frame decodes and its CRC checks out.
+pub struct FrameDecoder {
+    buf: Vec<u8, FRAME_MAX>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    /// `Ok(None)` means the frame isn't complete yet; keep feeding bytes.
+    pub fn push_byte(&mut self, byte: u8) -> Result<Option<Message>, FrameError> {
+        if byte == 0x00 {
+            // A delimiter with nothing accumulated (e.g. back-to-back
+            // delimiters, or the first byte after a resync) is normal, not
+            // a malformed frame.
+            if self.buf.is_empty() {
+                return Ok(None);
+            }
+            let result = self.decode_frame();
+            self.buf.clear();
+            return result.map(Some);
+        }
+        if self.buf.push(byte).is_err() {
+            self.buf.clear();
+            return Err(FrameError::Overrun);
+        }
+        Ok(None)
+    }
+
+    fn decode_frame(&self) -> Result<Message, FrameError> {
+        if self.buf.is_empty() {
+            return Err(FrameError::Truncated);
+        }
+
+        let mut raw: Vec<u8, RAW_MAX> = Vec::new();
+        cobs_decode(&self.buf, &mut raw)?;
+
+        if raw.len() < 3 + 2 {
+            return Err(FrameError::Truncated);
+        }
+        let crc_offset = raw.len() - 2;
+        let received_crc = u16::from_be_bytes([raw[crc_offset], raw[crc_offset + 1]]);
+        if crc16_ccitt(&raw[..crc_offset]) != received_crc {
+            return Err(FrameError::BadCrc);
+        }
+
+        let id = u16::from_be_bytes([raw[0], raw[1]]);
+        let len = raw[2] as usize;
+        let payload_end = 3 + len;
+        if payload_end != crc_offset {
+            return Err(FrameError::Truncated);
+        }
+
+        let mut message = Message::new(id);
+        message
+            .add_data(&raw[3..payload_end])
+            .map_err(|_| FrameError::Overrun)?;
+        Ok(message)
+    }
+}
+
+/// CRC-16/CCITT-FALSE: poly 0x1021, init 0xFFFF, MSB first.
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Splits `input` into runs between zero bytes and prefixes each run with a
+/// length-plus-one code byte (max `0xFF` = 254 data bytes per block,
+/// inserting an overhead block boundary instead of waiting for a zero when
+/// a run hits that cap).
+fn cobs_encode(input: &[u8], out: &mut Vec<u8, FRAME_MAX>) -> Result<(), ()> {
+    let mut code_idx = out.len();
+    out.push(0).map_err(|_| ())?;
+    let mut code: u8 = 1;
+
+    for &byte in input {
+        if byte == 0 {
+            out[code_idx] = code;
+            code_idx = out.len();
+            out.push(0).map_err(|_| ())?;
+            code = 1;
+        } else {
+            out.push(byte).map_err(|_| ())?;
+            code += 1;
+            if code == 0xFF {
+                out[code_idx] = code;
+                code_idx = out.len();
+                out.push(0).map_err(|_| ())?;
+                code = 1;
+            }
+        }
+    }
+    out[code_idx] = code;
+    Ok(())
+}
+
+/// Reads a code byte, copies `code - 1` data bytes, then emits a zero
+/// unless the code was `0xFF` (the encoder's marker for "block was capped,
+/// no zero here") or the block was the last one in `input`.
+fn cobs_decode(input: &[u8], out: &mut Vec<u8, RAW_MAX>) -> Result<(), FrameError> {
+    let mut i = 0;
+    while i < input.len() {
+        let code = input[i] as usize;
+        if code == 0 {
+            return Err(FrameError::BadCobs);
+        }
+        i += 1;
+        let end = i + (code - 1);
+        if end > input.len() {
+            return Err(FrameError::BadCobs);
+        }
+        out.extend_from_slice(&input[i..end]).map_err(|_| FrameError::Overrun)?;
+        i = end;
+        if code != 0xFF && i < input.len() {
+            out.push(0).map_err(|_| FrameError::Overrun)?;
+        }
+    }
+    Ok(())
 }
\ No newline at end of file